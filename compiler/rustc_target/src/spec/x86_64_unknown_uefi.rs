@@ -20,8 +20,12 @@ pub fn target() -> Target {
     // why we avoid them and instead use soft-floats. This is also what GRUB and friends did so
     // far.
     //
-    // If you initialize FP units yourself, you can override these flags with custom linker
-    // arguments, thus giving you access to full MMX/SSE acceleration.
+    // If you initialize FP units yourself, you can override these flags with
+    // `-C target-feature=-soft-float,+mmx,+sse,+sse2`, thus giving you access to full MMX/SSE
+    // acceleration. Since this changes the float ABI, all crates including `core` have to be
+    // built with the same flags (e.g., via `-Zbuild-std`). rustc warns about `soft-float` and
+    // `mmx` being unknown features, but still passes them on to LLVM. See the platform-support
+    // documentation for details.
     base.features = "-mmx,-sse,+soft-float".into();
 
     Target {
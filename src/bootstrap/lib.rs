@@ -1227,6 +1227,11 @@ fn qemu_rootfs(&self, target: TargetSelection) -> Option<&Path> {
         self.config.target_config.get(&target).and_then(|t| t.qemu_rootfs.as_ref()).map(|p| &**p)
     }
 
+    /// Returns the runner for test binaries of `target`, passed to compiletest as `--runtool`.
+    fn runner(&self, target: TargetSelection) -> Option<&str> {
+        self.config.target_config.get(&target).and_then(|t| t.runner.as_deref())
+    }
+
     /// Path to the python interpreter to use
     fn python(&self) -> &Path {
         if self.config.build.ends_with("apple-darwin") {
@@ -1476,6 +1476,18 @@ fn run(self, builder: &Builder<'_>) {
         if let Some(ref npm) = builder.config.npm {
             cmd.arg("--npm").arg(npm);
         }
+        if let Some(runner) = builder.runner(target) {
+            // compiletest would upload the runner to the remote device as the test program
+            if builder.remote_tested(target) {
+                eprintln!(
+                    "error: `runner` for {} cannot be combined with remote testing \
+                     (`qemu-rootfs`, `TEST_DEVICE_ADDR` or android)",
+                    target
+                );
+                crate::detail_exit(1);
+            }
+            cmd.arg("--runtool").arg(runner);
+        }
         if builder.config.rust_optimize_tests {
             cmd.arg("--optimize-tests");
         }
@@ -0,0 +1,185 @@
+#!/usr/bin/env python3
+
+"""
+Runs a UEFI application in QEMU with OVMF/AAVMF and reports its output and
+exit status, for use as compiletest's `--runtool` (see the `runner` option in
+config.toml.example).
+
+Usage: uefi-qemu-runner.py <image.efi> [args...]
+
+The image is copied onto a FAT drive together with a `startup.nsh` script,
+which the UEFI Shell embedded in the firmware runs on boot. The script starts
+the image with the given arguments, prints the returned status and powers the
+machine off. Everything the image writes to the console between the start and
+exit markers is forwarded to stdout.
+
+The exit status is 0 if the image returned EFI_SUCCESS and 1 otherwise. If the
+VM stops without printing one of the markers, e.g. because the firmware has no
+shell, the image reset the machine itself or the timeout expired, the exit
+status is 2.
+
+The firmware files default to the locations of Ubuntu's `ovmf` and
+`qemu-efi-aarch64` packages, and can be overridden with the
+`UEFI_QEMU_FIRMWARE_CODE` and `UEFI_QEMU_FIRMWARE_VARS` environment variables.
+The firmware build must include the UEFI Shell. The only 32-bit OVMF packaged
+by Debian/Ubuntu (`ovmf-ia32`) is a secure-boot build without the shell, so
+there is no default for i686 and `UEFI_QEMU_FIRMWARE_CODE` (and usually
+`UEFI_QEMU_FIRMWARE_VARS`) has to point at an IA32 OVMF built with the shell,
+e.g. from EDK2's `OvmfPkg/OvmfPkgIa32.dsc`. `UEFI_QEMU_TIMEOUT` sets the
+timeout in seconds (default: 300).
+"""
+
+import os
+import re
+import shutil
+import struct
+import subprocess
+import sys
+import tempfile
+
+from pathlib import Path
+
+START_MARKER = '@@UEFI-RUNNER-START@@'
+EXIT_MARKER = '@@UEFI-RUNNER-EXIT@@'
+
+# PE machine types, see the "Machine Types" section of the PE format
+# specification.
+IMAGE_FILE_MACHINE_I386 = 0x014c
+IMAGE_FILE_MACHINE_AMD64 = 0x8664
+IMAGE_FILE_MACHINE_ARM64 = 0xaa64
+
+# Same settings as src/ci/docker/host-x86_64/test-various/uefi_qemu_test/run.py
+MACHINES = {
+    IMAGE_FILE_MACHINE_AMD64: {
+        'qemu': 'qemu-system-x86_64',
+        'machine': 'q35',
+        'cpu': 'qemu64',
+        'code': '/usr/share/OVMF/OVMF_CODE.fd',
+        'vars': '/usr/share/OVMF/OVMF_VARS.fd',
+    },
+    # The i686 target uses 64-bit qemu; the important difference is that the
+    # OVMF code provides a 32-bit environment. The packaged 32-bit OVMF is a
+    # secure-boot build without the UEFI Shell, so the code has to be provided
+    # through `UEFI_QEMU_FIRMWARE_CODE`.
+    IMAGE_FILE_MACHINE_I386: {
+        'qemu': 'qemu-system-x86_64',
+        'machine': 'q35',
+        'cpu': 'qemu64',
+        'code': None,
+        'vars': '/usr/share/OVMF/OVMF32_VARS_4M.fd',
+    },
+    IMAGE_FILE_MACHINE_ARM64: {
+        'qemu': 'qemu-system-aarch64',
+        'machine': 'virt',
+        'cpu': 'cortex-a72',
+        'code': '/usr/share/AAVMF/AAVMF_CODE.fd',
+        'vars': '/usr/share/AAVMF/AAVMF_VARS.fd',
+    },
+}
+
+def pe_machine(path):
+    """Return the machine type from the COFF header of a PE image."""
+    with open(path, 'rb') as f:
+        data = f.read(4096)
+    if data[:2] != b'MZ':
+        raise ValueError('{} is not a PE image'.format(path))
+    pe_offset = struct.unpack_from('<I', data, 0x3c)[0]
+    if data[pe_offset:pe_offset + 4] != b'PE\0\0':
+        raise ValueError('{} is not a PE image'.format(path))
+    return struct.unpack_from('<H', data, pe_offset + 4)[0]
+
+def shell_quote(arg):
+    """Quote an argument for the UEFI Shell, where `^` is the escape character."""
+    return '"{}"'.format(re.sub(r'([\^"%])', r'^\1', arg))
+
+def write_startup_script(path, args):
+    cmd = ' '.join(['fs0:\\test.efi'] + [shell_quote(arg) for arg in args])
+    script = '\r\n'.join([
+        '@echo -off',
+        'fs0:',
+        'echo "{}"'.format(START_MARKER),
+        cmd,
+        'echo "{} %lasterror%"'.format(EXIT_MARKER),
+        'reset -s',
+        '',
+    ])
+    path.write_text(script, encoding='utf-8')
+
+def main():
+    if len(sys.argv) < 2:
+        sys.exit('usage: {} <image.efi> [args...]'.format(sys.argv[0]))
+    image = Path(sys.argv[1])
+    args = sys.argv[2:]
+
+    machine = MACHINES.get(pe_machine(image))
+    if machine is None:
+        sys.exit('unsupported PE machine type in {}'.format(image))
+    code = os.environ.get('UEFI_QEMU_FIRMWARE_CODE', machine['code'])
+    if code is None:
+        sys.exit('no default firmware with a UEFI Shell for {}, '
+                 'set UEFI_QEMU_FIRMWARE_CODE'.format(image))
+    vars_file = os.environ.get('UEFI_QEMU_FIRMWARE_VARS', machine['vars'])
+    timeout = int(os.environ.get('UEFI_QEMU_TIMEOUT', '300'))
+
+    with tempfile.TemporaryDirectory() as tmp_dir:
+        tmp_dir = Path(tmp_dir)
+
+        # Create a mock EFI System Partition with the image and the startup
+        # script. There is no `efi/boot/boot*.efi`, so the firmware falls back
+        # to the UEFI Shell, which runs `startup.nsh`.
+        esp = tmp_dir / 'esp'
+        esp.mkdir()
+        shutil.copy(image, esp / 'test.efi')
+        write_startup_script(esp / 'startup.nsh', args)
+
+        # Make a writable copy of the vars file. aarch64 doesn't boot
+        # correctly with read-only vars.
+        rw_vars = tmp_dir / 'vars.fd'
+        shutil.copy(vars_file, rw_vars)
+
+        cmd = [
+            machine['qemu'],
+            '-machine', machine['machine'],
+            '-cpu', machine['cpu'],
+            '-display', 'none',
+            '-serial', 'stdio',
+            '-net', 'none',
+            '-drive', 'if=pflash,format=raw,readonly=on,file={}'.format(code),
+            '-drive', 'if=pflash,format=raw,readonly=off,file={}'.format(rw_vars),
+            '-drive', 'format=raw,file=fat:rw:{}'.format(esp),
+        ]
+        try:
+            output = subprocess.run(cmd,
+                                    stdout=subprocess.PIPE,
+                                    stdin=subprocess.DEVNULL,
+                                    errors='replace',
+                                    timeout=timeout).stdout
+        except subprocess.TimeoutExpired as e:
+            output = e.stdout or ''
+            if isinstance(output, bytes):
+                output = output.decode('utf-8', errors='replace')
+            print('uefi-qemu-runner: timed out after {}s'.format(timeout),
+                  file=sys.stderr)
+
+    output = output.replace('\r\n', '\n')
+    start = output.find(START_MARKER + '\n')
+    if start < 0:
+        sys.stdout.write(output)
+        print('uefi-qemu-runner: the UEFI Shell did not start the image',
+              file=sys.stderr)
+        sys.exit(2)
+    output = output[start + len(START_MARKER) + 1:]
+
+    match = re.search(re.escape(EXIT_MARKER) + r' (0x[0-9a-fA-F]+|\d+)', output)
+    if match is None:
+        sys.stdout.write(output)
+        print('uefi-qemu-runner: the image did not return to the shell',
+              file=sys.stderr)
+        sys.exit(2)
+
+    sys.stdout.write(output[:match.start()])
+    sys.stdout.flush()
+    sys.exit(0 if int(match.group(1), 0) == 0 else 1)
+
+if __name__ == '__main__':
+    main()
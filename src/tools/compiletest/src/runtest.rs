@@ -2057,6 +2057,8 @@ fn make_exe_name(&self) -> PathBuf {
             f = f.with_extra_extension("wasm");
         } else if self.config.target.contains("spirv") {
             f = f.with_extra_extension("spv");
+        } else if self.config.target.contains("uefi") {
+            f = f.with_extra_extension("efi");
         } else if !env::consts::EXE_SUFFIX.is_empty() {
             f = f.with_extra_extension(env::consts::EXE_SUFFIX);
         }
@@ -2094,6 +2096,10 @@ fn make_run_args(&self) -> ProcArgs {
                 .parent()
                 .unwrap(); // chop off `src`
             args.push(src.join("src/etc/wasm32-shim.js").display().to_string());
+        // UEFI binaries cannot be executed on the host, they have to be booted in
+        // firmware (e.g. QEMU with OVMF) by the runtool
+        } else if self.config.target.contains("uefi") && args.is_empty() {
+            self.fatal("no runtool configured for UEFI target (--runtool)");
         }
 
         let exe_file = self.make_exe_name();